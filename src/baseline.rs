@@ -0,0 +1,99 @@
+use crate::error::FlakyFinderResult;
+use serde::Deserialize;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+/// The expected, non-flaky outcome of a run: an exit code and, optionally, a
+/// fingerprint of stdout. Runs that drift from this are what make a command
+/// flaky rather than just broken.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Baseline {
+    pub(crate) exit_code: i32,
+    #[serde(default)]
+    pub(crate) stdout_fingerprint: Option<u64>,
+}
+
+impl Baseline {
+    /// The default baseline, used when no `--baseline` file is given: the
+    /// conventional passing exit code, `0`. The warm-up run still happens
+    /// (e.g. to prime compilation), but its own exit code isn't trusted as
+    /// the expectation — a command that fails deterministically on every
+    /// run, including the warm-up, must not be handed itself as the
+    /// baseline, or every run would "match" and get reported as a clean
+    /// pass instead of `ConsistentFail`. A command that's expected to fail
+    /// needs an explicit `--baseline` file to say so.
+    pub(crate) fn from_warmup() -> Self {
+        Self {
+            exit_code: 0,
+            stdout_fingerprint: None,
+        }
+    }
+
+    /// Load an explicit expectations file, e.g. for a command that's known to
+    /// always fail in a specific way and shouldn't be reported as flaky.
+    pub(crate) fn load(path: &Path) -> FlakyFinderResult<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Whether a run's outcome matches this baseline.
+    pub(crate) fn matches(&self, exit_code: Option<i32>, stdout: &[u8]) -> bool {
+        if exit_code != Some(self.exit_code) {
+            return false;
+        }
+        match self.stdout_fingerprint {
+            Some(expected) => fingerprint(stdout) == expected,
+            None => true,
+        }
+    }
+}
+
+/// How the command behaved across every run, relative to its baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Classification {
+    /// Every run matched the baseline.
+    Pass,
+    /// Some runs matched the baseline and some didn't: the actual flakiness signal.
+    Flake,
+    /// No run matched the baseline: the command is simply broken, not flaky.
+    ConsistentFail,
+}
+
+/// A cheap, non-cryptographic fingerprint used to detect stdout drift between runs.
+fn fingerprint(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_warmup_ignores_stdout() {
+        let baseline = Baseline::from_warmup();
+        assert!(baseline.matches(Some(0), b"anything"));
+        assert!(baseline.matches(Some(0), b"something else"));
+    }
+
+    #[test]
+    fn matches_checks_exit_code_first() {
+        let baseline = Baseline::from_warmup();
+        assert!(!baseline.matches(Some(1), b"anything"));
+        assert!(!baseline.matches(None, b"anything"));
+    }
+
+    #[test]
+    fn matches_checks_stdout_fingerprint_when_set() {
+        let baseline = Baseline {
+            exit_code: 0,
+            stdout_fingerprint: Some(fingerprint(b"expected")),
+        };
+        assert!(baseline.matches(Some(0), b"expected"));
+        assert!(!baseline.matches(Some(0), b"unexpected"));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::cli::Opt;
+use crate::error::FlakyFinderResult;
+use crate::report::ReportFormat;
+use crate::FlakyFinder;
+use std::path::PathBuf;
+use std::time::Duration;
+use structopt::StructOpt;
+
+#[derive(Debug, Default)]
+pub(crate) struct FlakyFinderBuilder {
+    cmd: Option<String>,
+    nb_threads: Option<u32>,
+    max_threads: Option<u32>,
+    runs: Option<u64>,
+    should_continue: Option<bool>,
+    show_errors_as_summary: Option<bool>,
+    timeout: Option<Duration>,
+    stream: Option<bool>,
+    report_path: Option<PathBuf>,
+    report_format: Option<ReportFormat>,
+    baseline_path: Option<PathBuf>,
+}
+
+impl FlakyFinderBuilder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a [`FlakyFinderBuilder`] straight from the process' command line arguments.
+    pub(crate) fn from_cli() -> FlakyFinderResult<Self> {
+        let opt = Opt::from_args();
+        let mut builder = Self::new()
+            .cmd(&opt.cmd)
+            .max_threads(opt.max_threads)
+            .runs(opt.runs)
+            .should_continue(opt.should_continue)
+            .show_errors_as_summary(opt.show_errors_as_summary)
+            .stream(opt.stream)
+            .report_format(opt.format);
+
+        if let Some(nb_threads) = opt.nb_threads {
+            builder = builder.nb_threads(nb_threads);
+        }
+
+        if let Some(timeout) = opt.timeout {
+            builder = builder.timeout(humantime::parse_duration(&timeout)?);
+        }
+
+        if let Some(report) = opt.report {
+            builder = builder.report_path(report);
+        }
+
+        if let Some(baseline) = opt.baseline {
+            builder = builder.baseline_path(baseline);
+        }
+
+        Ok(builder)
+    }
+
+    pub(crate) fn cmd(mut self, cmd: &str) -> Self {
+        self.cmd = Some(cmd.to_string());
+        self
+    }
+
+    pub(crate) fn nb_threads(mut self, nb_threads: u32) -> Self {
+        self.nb_threads = Some(nb_threads);
+        self
+    }
+
+    pub(crate) fn max_threads(mut self, max_threads: u32) -> Self {
+        self.max_threads = Some(max_threads);
+        self
+    }
+
+    pub(crate) fn runs(mut self, runs: u64) -> Self {
+        self.runs = Some(runs);
+        self
+    }
+
+    pub(crate) fn should_continue(mut self, should_continue: bool) -> Self {
+        self.should_continue = Some(should_continue);
+        self
+    }
+
+    pub(crate) fn show_errors_as_summary(mut self, show_errors_as_summary: bool) -> Self {
+        self.show_errors_as_summary = Some(show_errors_as_summary);
+        self
+    }
+
+    pub(crate) fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub(crate) fn stream(mut self, stream: bool) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    pub(crate) fn report_path(mut self, report_path: PathBuf) -> Self {
+        self.report_path = Some(report_path);
+        self
+    }
+
+    pub(crate) fn report_format(mut self, report_format: ReportFormat) -> Self {
+        self.report_format = Some(report_format);
+        self
+    }
+
+    pub(crate) fn baseline_path(mut self, baseline_path: PathBuf) -> Self {
+        self.baseline_path = Some(baseline_path);
+        self
+    }
+
+    pub(crate) fn build(self) -> FlakyFinder {
+        // `--max-threads` only bounds the auto-detected default; an explicit
+        // `--nb-threads` is a deliberate override and is used as-is.
+        let max_threads = self.max_threads.unwrap_or(64);
+        let nb_threads = self
+            .nb_threads
+            .unwrap_or_else(|| (num_cpus::get() as u32).min(max_threads));
+
+        FlakyFinder {
+            cmd: self.cmd.expect("A command to run is required."),
+            exit_status: None,
+            results: Vec::new(),
+            nb_threads,
+            runs: self.runs.unwrap_or(100),
+            should_continue: self.should_continue.unwrap_or(true),
+            show_errors_as_summary: self.show_errors_as_summary.unwrap_or(false),
+            timeout: self.timeout,
+            stream: self.stream.unwrap_or(false),
+            report_path: self.report_path,
+            report_format: self.report_format.unwrap_or(ReportFormat::Json),
+            baseline_path: self.baseline_path,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::report::ReportFormat;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Run a command multiple times concurrently and report on any
+/// non-deterministic ("flaky") failures.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "flaky-finder")]
+pub(crate) struct Opt {
+    /// The command to run, e.g. "cargo test -- --nocapture release_test".
+    #[structopt(short, long)]
+    pub(crate) cmd: String,
+
+    /// Number of threads to run the command concurrently with. Defaults to
+    /// the detected CPU count, clamped to `--max-threads`.
+    #[structopt(short = "t", long)]
+    pub(crate) nb_threads: Option<u32>,
+
+    /// Upper bound on the thread pool size when `--nb-threads` isn't given
+    /// explicitly; past this point more threads tend to just add lock/IO
+    /// contention rather than speed things up.
+    #[structopt(long, default_value = "64")]
+    pub(crate) max_threads: u32,
+
+    /// Number of times the command should be run.
+    #[structopt(short, long, default_value = "100")]
+    pub(crate) runs: u64,
+
+    /// Keep going after the first failing run instead of stopping immediately.
+    #[structopt(short = "k", long)]
+    pub(crate) should_continue: bool,
+
+    /// Only print the errors found as a summary at the end, instead of as they happen.
+    #[structopt(short, long)]
+    pub(crate) show_errors_as_summary: bool,
+
+    /// Per-run wall clock timeout, e.g. `30s` or `1m`. Runs that exceed it are
+    /// killed and reported as timeouts rather than ordinary failures.
+    #[structopt(long)]
+    pub(crate) timeout: Option<String>,
+
+    /// Stream each run's stdout/stderr live, prefixed with its run id, instead
+    /// of only showing captured output once a run completes.
+    #[structopt(long)]
+    pub(crate) stream: bool,
+
+    /// Write a machine-readable report of every run to this path.
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) report: Option<PathBuf>,
+
+    /// Format to use for the `--report` output.
+    #[structopt(long, default_value = "json")]
+    pub(crate) format: ReportFormat,
+
+    /// Baseline/expectations file (JSON: `{"exit_code": N}`) declaring the
+    /// exit code (and optionally a stdout fingerprint) that should be
+    /// treated as expected. Without it, the expected exit code is `0`; the
+    /// warm-up run's own exit code is not used.
+    #[structopt(long, parse(from_os_str))]
+    pub(crate) baseline: Option<PathBuf>,
+}
@@ -0,0 +1,41 @@
+use std::{fmt, io};
+
+pub(crate) type FlakyFinderResult<T> = Result<T, FlakyFinderError>;
+
+/// Errors that can occur while driving the command under test.
+#[derive(Debug)]
+pub(crate) enum FlakyFinderError {
+    Io(io::Error),
+    InvalidDuration(humantime::DurationError),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for FlakyFinderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlakyFinderError::Io(e) => write!(f, "IO error: {}", e),
+            FlakyFinderError::InvalidDuration(e) => write!(f, "invalid --timeout value: {}", e),
+            FlakyFinderError::Json(e) => write!(f, "JSON error (report or baseline file): {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FlakyFinderError {}
+
+impl From<io::Error> for FlakyFinderError {
+    fn from(e: io::Error) -> Self {
+        FlakyFinderError::Io(e)
+    }
+}
+
+impl From<humantime::DurationError> for FlakyFinderError {
+    fn from(e: humantime::DurationError) -> Self {
+        FlakyFinderError::InvalidDuration(e)
+    }
+}
+
+impl From<serde_json::Error> for FlakyFinderError {
+    fn from(e: serde_json::Error) -> Self {
+        FlakyFinderError::Json(e)
+    }
+}
@@ -2,25 +2,50 @@
 use crate::utils::{fstderr, fstdout};
 use builder::FlakyFinderBuilder;
 use error::FlakyFinderResult;
+use baseline::{Baseline, Classification};
 use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
+use report::{Report, ReportFormat, RunResult};
+use std::os::unix::process::ExitStatusExt;
 use std::{
-    io::{stdout, Write},
-    process::{Command, ExitStatus, Output},
+    io::{stdout, BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{Command, ExitStatus, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
 };
 
+mod baseline;
 mod builder;
 mod cli;
 mod error;
+mod report;
 mod utils;
 
+/// The exit code `timeout(1)` itself uses to report that it had to kill the
+/// child; we reuse it so a timed out run still "looks like" a normal failure
+/// to anything inspecting the exit status.
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// How often we poll a running child for completion while watching for its timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The result of a single invocation of the command under test.
+#[derive(Debug, Clone)]
+struct JobResult {
+    index: u64,
+    output: Output,
+    timed_out: bool,
+    duration: Duration,
+}
+
 #[derive(Debug)]
 pub(crate) struct FlakyFinder {
     /// The actual command that we need to test.
     cmd: String,
     /// The status of the process we are currently evaluating
     exit_status: Option<ExitStatus>,
-    /// The output from the process we are evaluating: stdout/stderr
-    outputs: Vec<Output>,
+    /// The result of every run we have performed so far.
+    results: Vec<RunResult>,
     /// Let's run those tests in parallel
     nb_threads: u32,
     /// How many times we should run the command.
@@ -29,6 +54,19 @@ pub(crate) struct FlakyFinder {
     should_continue: bool,
     /// Should we show the errors as they come or only in the end as a summary
     show_errors_as_summary: bool,
+    /// Wall-clock limit for a single run. Runs that exceed it are killed and
+    /// classified as timeouts rather than ordinary failures.
+    timeout: Option<Duration>,
+    /// Stream each run's stdout/stderr live instead of only showing it once
+    /// captured output is available at the end of a run.
+    stream: bool,
+    /// Where to write a machine-readable report of every run, if requested.
+    report_path: Option<PathBuf>,
+    /// The format to write the report in.
+    report_format: ReportFormat,
+    /// Optional expectations file establishing the baseline to compare runs
+    /// against. Without it, the baseline is exit code `0`.
+    baseline_path: Option<PathBuf>,
 }
 
 impl FlakyFinder {
@@ -44,66 +82,104 @@ impl FlakyFinder {
                 "{spinner:.cyan} [{elapsed_precise}] [{bar:40.white/gray}] ({pos}/{len}, ETA {eta}) {msg}",
         ));
 
-        let (sx, rx) = crossbeam_channel::bounded(runs as usize);
+        // Bound to roughly the pool size rather than `runs`, so a huge
+        // `--runs` value doesn't buffer millions of pending results in memory.
+        let (sx, rx) = crossbeam_channel::bounded(nb_threads as usize);
 
         let cmd = std::sync::Arc::new(cmd.to_string());
 
         // Execute the process at least one time in order to single process the compilation
         print!(">> Warming up...");
         stdout().flush()?;
-        let _ = Command::new("sh")
+        let _warmup_output = Command::new("sh")
             .arg("-c")
             .arg(cmd.to_string())
             .output()
             .expect("Fail to warming up.");
         println!("done.");
 
+        let baseline = match &self.baseline_path {
+            Some(path) => Baseline::load(path)?,
+            // The warm-up run's own exit code isn't trusted as the baseline:
+            // a deterministically-failing command would otherwise become
+            // its own (always-matching) expectation. `--baseline` is the
+            // opt-in for a command that's expected not to exit 0.
+            None => Baseline::from_warmup(),
+        };
+
         let pool = threadpool::ThreadPool::new(nb_threads as usize);
+        let timeout = self.timeout;
+        let stream = self.stream;
 
-        for _ in 0..runs {
+        for index in 0..runs {
             let cmd = cmd.clone();
             let sx = sx.clone();
+            let pb = pb.clone();
 
             pool.execute(move || {
-                let output = Command::new("sh")
-                    .arg("-c")
-                    .arg(cmd.to_string())
-                    .output()
+                let job_result = Self::run_one(&cmd, index, timeout, stream, &pb)
                     .expect("Fail to run command process.");
 
-                sx.send(output)
-                    .expect("Fail to send Command's output to channel.");
+                // The receiving end is dropped as soon as the main loop
+                // breaks on the first failure (when `-k`/--should-continue
+                // isn't set), which happens routinely with the channel now
+                // bounded to the thread pool size rather than `runs`. A
+                // blocked or future send from a still-running job is then
+                // expected to fail, not panic a worker thread.
+                let _ = sx.send(job_result);
             });
         }
 
         drop(sx);
 
         let mut error_counter = 0;
-        for recv_output in rx.iter().progress_with(pb.clone()) {
-            let status = recv_output.status;
+        let mut timeout_counter = 0;
+        for recv_result in rx.iter().progress_with(pb.clone()) {
+            let status = recv_result.output.status;
+            let run_result = RunResult {
+                index: recv_result.index,
+                exit_code: status.code(),
+                timed_out: recv_result.timed_out,
+                duration_ms: recv_result.duration.as_millis(),
+                matches_baseline: baseline.matches(status.code(), &recv_result.output.stdout),
+                stdout: String::from_utf8_lossy(&recv_result.output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&recv_result.output.stderr).into_owned(),
+            };
+
+            if recv_result.timed_out {
+                timeout_counter += 1;
+            }
+
+            let should_break = !status.success() && !self.should_continue;
+
             if !status.success() {
                 error_counter += 1;
 
-                if !self.should_continue {
-                    break;
-                } else {
+                if self.should_continue {
                     pb.set_message(&format!(
-                        "-- {:.*}% Errors found...",
+                        "-- {:.*}% errors, {:.*}% timeouts found...",
                         1,
-                        self.percent_of_error_found(error_counter)
+                        self.percent_of_error_found(error_counter),
+                        1,
+                        self.percent_of_error_found(timeout_counter)
                     ));
                     pb.inc(0);
 
                     if !self.show_errors_as_summary {
-                        pb.println(::std::str::from_utf8(&recv_output.stdout)?);
-                        pb.println(::std::str::from_utf8(&recv_output.stderr)?);
+                        pb.println(&run_result.stdout);
+                        pb.println(&run_result.stderr);
                         pb.println(format!(
                             "\n{:^80}\n\n",
                             "##########################################"
                         ));
                     }
                 }
-                self.outputs.push(recv_output.clone());
+            }
+
+            self.results.push(run_result);
+
+            if should_break {
+                break;
             }
         }
 
@@ -117,24 +193,187 @@ impl FlakyFinder {
             self.show_errors()?;
         }
 
-        Ok(self.outputs.is_empty())
+        if timeout_counter > 0 {
+            eprintln!(
+                ">> {:.*}% of runs timed out ({} out of {}).",
+                1,
+                self.percent_of_error_found(timeout_counter),
+                timeout_counter,
+                self.runs
+            );
+        }
+
+        if let Some(report_path) = &self.report_path {
+            Report::new(&self.cmd, self.results.clone())
+                .write_to(report_path, self.report_format)?;
+        }
+
+        let classification = self.classify();
+        match classification {
+            Classification::Pass => eprintln!(">> No flakiness detected against the baseline."),
+            Classification::Flake => {
+                let mismatches = self.results.iter().filter(|r| !r.matches_baseline).count() as u64;
+                eprintln!(
+                    ">> {:.*}% flake rate ({} out of {} runs differ from the baseline intermittently).",
+                    1,
+                    self.percent_of_error_found(mismatches),
+                    mismatches,
+                    self.runs
+                );
+            }
+            Classification::ConsistentFail => eprintln!(
+                ">> Every run differs from the baseline: this command is consistently failing, not flaky."
+            ),
+        }
+
+        Ok(classification == Classification::Pass)
+    }
+
+    /// Classify the command's overall behavior across every run by comparing
+    /// against the baseline: matching every run is a pass, matching none is a
+    /// consistent failure (not flaky, just broken), and anything in between
+    /// is the actual flakiness signal.
+    fn classify(&self) -> Classification {
+        if self.results.is_empty() {
+            // No runs were collected at all (e.g. `--runs 0`): there's nothing
+            // to compare against the baseline, so this is not a pass.
+            return Classification::ConsistentFail;
+        }
+        let mismatches = self.results.iter().filter(|r| !r.matches_baseline).count();
+        if mismatches == 0 {
+            Classification::Pass
+        } else if mismatches == self.results.len() {
+            Classification::ConsistentFail
+        } else {
+            Classification::Flake
+        }
+    }
+
+    /// Run the command once, enforcing `timeout` if set.
+    ///
+    /// Spawns the process rather than blocking on `Command::output()` so that
+    /// a hung run can be killed instead of stalling the whole pool. On
+    /// expiry, the child is killed and reaped, and the result is tagged as a
+    /// timeout rather than a normal non-zero exit. stdout/stderr are always
+    /// drained on their own threads while we wait, exactly like
+    /// `Command::output()` would, so a child that fills the OS pipe buffer
+    /// can't deadlock the timeout loop; when `stream` is set, each line is
+    /// also forwarded to `pb` as it arrives.
+    fn run_one(
+        cmd: &str,
+        index: u64,
+        timeout: Option<Duration>,
+        stream: bool,
+        pb: &ProgressBar,
+    ) -> FlakyFinderResult<JobResult> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout_pipe = child.stdout.take().expect("stdout was piped");
+        let stderr_pipe = child.stderr.take().expect("stderr was piped");
+        let pb_out = pb.clone();
+        let pb_err = pb.clone();
+        let stdout_handle =
+            thread::spawn(move || Self::forward_stream(stdout_pipe, index, stream, pb_out));
+        let stderr_handle =
+            thread::spawn(move || Self::forward_stream(stderr_pipe, index, stream, pb_err));
+
+        let start = Instant::now();
+        let mut timed_out = false;
+        let mut status = if let Some(timeout) = timeout {
+            loop {
+                if let Some(status) = child.try_wait()? {
+                    break status;
+                }
+
+                if start.elapsed() >= timeout {
+                    child.kill()?;
+                    timed_out = true;
+                    break child.wait()?;
+                }
+
+                thread::sleep(POLL_INTERVAL);
+            }
+        } else {
+            // No timeout to watch for: block on the child directly instead
+            // of polling, so the common case doesn't pay up to POLL_INTERVAL
+            // of added latency per run.
+            child.wait()?
+        };
+
+        let duration = start.elapsed();
+
+        if timed_out {
+            status = ExitStatus::from_raw(TIMEOUT_EXIT_CODE << 8);
+        }
+
+        let stdout = stdout_handle.join().expect("stdout forwarder panicked")?;
+        let stderr = stderr_handle.join().expect("stderr forwarder panicked")?;
+
+        Ok(JobResult {
+            index,
+            output: Output {
+                status,
+                stdout,
+                stderr,
+            },
+            timed_out,
+            duration,
+        })
+    }
+
+    /// Drain a run's output as it arrives. When `stream` is set, each line is
+    /// also forwarded to the progress bar, prefixed with its run id so
+    /// interleaved runs stay readable; the raw bytes are always captured for
+    /// the final report regardless. Reads raw bytes rather than `String`
+    /// lines so a child emitting invalid UTF-8 (e.g. binary output) can't
+    /// crash the worker; streamed lines are decoded lossily for display only.
+    fn forward_stream<R: Read + Send + 'static>(
+        pipe: R,
+        index: u64,
+        stream: bool,
+        pb: ProgressBar,
+    ) -> FlakyFinderResult<Vec<u8>> {
+        let mut reader = BufReader::new(pipe);
+        let mut captured = Vec::new();
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            captured.extend_from_slice(&line);
+            if stream {
+                let text = String::from_utf8_lossy(&line);
+                pb.println(format!("[run {}] {}", index, text.trim_end_matches('\n')));
+            }
+        }
+
+        Ok(captured)
     }
 
     /// Print out all the errors we found.
     fn show_errors(&self) -> FlakyFinderResult<()> {
-        if self.outputs.is_empty() {
+        let failures: Vec<&RunResult> = self.results.iter().filter(|r| !r.success()).collect();
+
+        if failures.is_empty() {
             eprintln!(">> Nothing found 👍");
         } else {
             eprintln!(
                 "\n>> {:.*}% Errors found:",
                 1,
-                self.percent_of_error_found(self.outputs.len() as u64)
+                self.percent_of_error_found(failures.len() as u64)
             );
         }
-        for error_output in self.outputs.iter() {
-            fstdout(&error_output.stdout)?;
-            fstderr(&error_output.stderr)?;
-            if self.outputs.len() > 1 {
+        for failure in &failures {
+            fstdout(failure.stdout.as_bytes())?;
+            fstderr(failure.stderr.as_bytes())?;
+            if failures.len() > 1 {
                 eprintln!("\n{:^80}\n", "##########################################");
             }
         }
@@ -179,4 +418,46 @@ mod tests {
     fn failing_test() {
         assert!(false);
     }
+
+    fn run_result(index: u64, matches_baseline: bool) -> RunResult {
+        RunResult {
+            index,
+            exit_code: Some(if matches_baseline { 0 } else { 1 }),
+            timed_out: false,
+            duration_ms: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            matches_baseline,
+        }
+    }
+
+    fn classifying_ff(results: Vec<RunResult>) -> FlakyFinder {
+        let mut ff = FlakyFinderBuilder::new().cmd("ls").nb_threads(1).build();
+        ff.results = results;
+        ff
+    }
+
+    #[test]
+    fn classify_pass_when_every_run_matches_baseline() {
+        let ff = classifying_ff(vec![run_result(0, true), run_result(1, true)]);
+        assert_eq!(ff.classify(), Classification::Pass);
+    }
+
+    #[test]
+    fn classify_flake_when_some_runs_mismatch() {
+        let ff = classifying_ff(vec![run_result(0, true), run_result(1, false)]);
+        assert_eq!(ff.classify(), Classification::Flake);
+    }
+
+    #[test]
+    fn classify_consistent_fail_when_every_run_mismatches() {
+        let ff = classifying_ff(vec![run_result(0, false), run_result(1, false)]);
+        assert_eq!(ff.classify(), Classification::ConsistentFail);
+    }
+
+    #[test]
+    fn classify_consistent_fail_rather_than_pass_when_no_runs_were_collected() {
+        let ff = classifying_ff(Vec::new());
+        assert_eq!(ff.classify(), Classification::ConsistentFail);
+    }
 }
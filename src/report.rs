@@ -0,0 +1,187 @@
+use crate::error::FlakyFinderResult;
+use serde::Serialize;
+use std::{path::Path, str::FromStr};
+
+/// The file format a [`Report`] is written out as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReportFormat {
+    Json,
+    Junit,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ReportFormat::Json),
+            "junit" => Ok(ReportFormat::Junit),
+            other => Err(format!(
+                "unknown report format '{}', expected 'json' or 'junit'",
+                other
+            )),
+        }
+    }
+}
+
+/// The outcome of a single run of the command under test.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct RunResult {
+    pub(crate) index: u64,
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) timed_out: bool,
+    pub(crate) duration_ms: u128,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    /// Whether this run matched the established baseline.
+    pub(crate) matches_baseline: bool,
+}
+
+impl RunResult {
+    pub(crate) fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// An aggregate report over every run of the command under test, suitable
+/// for trending in CI.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Report {
+    cmd: String,
+    runs: u64,
+    failures: u64,
+    timeouts: u64,
+    failure_percentage: f32,
+    results: Vec<RunResult>,
+}
+
+impl Report {
+    pub(crate) fn new(cmd: &str, results: Vec<RunResult>) -> Self {
+        let runs = results.len() as u64;
+        let failures = results.iter().filter(|r| !r.success()).count() as u64;
+        let timeouts = results.iter().filter(|r| r.timed_out).count() as u64;
+        let failure_percentage = if runs == 0 {
+            0.0
+        } else {
+            (failures as f32 / runs as f32) * 100.0
+        };
+
+        Self {
+            cmd: cmd.to_string(),
+            runs,
+            failures,
+            timeouts,
+            failure_percentage,
+            results,
+        }
+    }
+
+    /// Write this report to `path`, formatted as `format`.
+    pub(crate) fn write_to(&self, path: &Path, format: ReportFormat) -> FlakyFinderResult<()> {
+        let rendered = match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)?,
+            ReportFormat::Junit => self.to_junit(),
+        };
+        std::fs::write(path, rendered)?;
+        Ok(())
+    }
+
+    fn to_junit(&self) -> String {
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            xml_escape(&self.cmd),
+            self.runs,
+            self.failures
+        );
+
+        for result in &self.results {
+            xml.push_str(&format!(
+                "  <testcase name=\"run {}\" classname=\"{}\" time=\"{:.3}\">\n",
+                result.index,
+                xml_escape(&self.cmd),
+                result.duration_ms as f64 / 1000.0
+            ));
+
+            if !result.success() {
+                let kind = if result.timed_out { "timeout" } else { "failure" };
+                let exit_code = result
+                    .exit_code
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                xml.push_str(&format!(
+                    "    <failure type=\"{}\" message=\"exit code {}\">{}\n{}</failure>\n",
+                    kind,
+                    exit_code,
+                    xml_escape(&result.stdout),
+                    xml_escape(&result.stderr)
+                ));
+            }
+
+            xml.push_str("  </testcase>\n");
+        }
+
+        xml.push_str("</testsuite>\n");
+        xml
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_result(index: u64, exit_code: Option<i32>, timed_out: bool) -> RunResult {
+        RunResult {
+            index,
+            exit_code,
+            timed_out,
+            duration_ms: 1500,
+            stdout: String::new(),
+            stderr: String::new(),
+            matches_baseline: exit_code == Some(0),
+        }
+    }
+
+    #[test]
+    fn xml_escape_escapes_special_characters() {
+        assert_eq!(
+            xml_escape("<a & b> \"c\""),
+            "&lt;a &amp; b&gt; &quot;c&quot;"
+        );
+    }
+
+    #[test]
+    fn report_new_computes_failure_percentage() {
+        let report = Report::new(
+            "ls",
+            vec![
+                run_result(0, Some(0), false),
+                run_result(1, Some(1), false),
+            ],
+        );
+        assert_eq!(report.runs, 2);
+        assert_eq!(report.failures, 1);
+        assert_eq!(report.failure_percentage, 50.0);
+    }
+
+    #[test]
+    fn to_junit_marks_timeouts_and_failures_distinctly() {
+        let report = Report::new(
+            "ls",
+            vec![
+                run_result(0, Some(0), false),
+                run_result(1, Some(124), true),
+            ],
+        );
+        let xml = report.to_junit();
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("type=\"timeout\""));
+        assert!(!xml.contains("type=\"failure\""));
+    }
+}
@@ -0,0 +1,11 @@
+use std::io::{self, Write};
+
+/// Write raw bytes captured from a child's stdout to our own stdout.
+pub(crate) fn fstdout(buf: &[u8]) -> io::Result<()> {
+    io::stdout().write_all(buf)
+}
+
+/// Write raw bytes captured from a child's stderr to our own stderr.
+pub(crate) fn fstderr(buf: &[u8]) -> io::Result<()> {
+    io::stderr().write_all(buf)
+}